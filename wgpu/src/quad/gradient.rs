@@ -3,7 +3,6 @@ use crate::graphics::gradient;
 use crate::quad::{self, Quad};
 
 use bytemuck::{Pod, Zeroable};
-use std::ops::Range;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum GradientRenderStrategy {
@@ -11,6 +10,54 @@ pub enum GradientRenderStrategy {
     Radial,
 }
 
+impl GradientRenderStrategy {
+    /// The `gradient_type` specialization-constant value selecting this
+    /// strategy's `find_t` branch in the shared gradient shader.
+    const fn constant(self) -> f64 {
+        match self {
+            GradientRenderStrategy::Linear => 0.0,
+            GradientRenderStrategy::Radial => 1.0,
+        }
+    }
+
+    /// A short label used to disambiguate pipeline and shader debug names.
+    const fn label(self) -> &'static str {
+        match self {
+            GradientRenderStrategy::Linear => "linear",
+            GradientRenderStrategy::Radial => "radial",
+        }
+    }
+}
+
+/// How a gradient behaves outside of its `[0, 1]` color-stop range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpreadMode {
+    /// Clamp to the first and last color stop (the default).
+    Pad,
+    /// Tile the gradient, repeating it from the start each period.
+    Repeat,
+    /// Tile the gradient, mirroring it every other period.
+    Reflect,
+}
+
+impl SpreadMode {
+    /// The per-instance value read by the shader's `apply_spread`, so a
+    /// single pipeline can mix differently-spread fills in one draw call.
+    const fn pack(self) -> u32 {
+        match self {
+            SpreadMode::Pad => 0,
+            SpreadMode::Repeat => 1,
+            SpreadMode::Reflect => 2,
+        }
+    }
+}
+
+impl Default for SpreadMode {
+    fn default() -> Self {
+        SpreadMode::Pad
+    }
+}
+
 #[derive(Debug)]
 /// A quad filled with interpolated colors.
 pub struct Gradient {
@@ -19,6 +66,24 @@ pub struct Gradient {
 
     /// The [`Quad`] data of the [`Gradient`].
     pub quad: Quad,
+
+    /// How the fill repeats outside of its `[0, 1]` color-stop range.
+    ///
+    /// This lives alongside `gradient: gradient::Packed` rather than inside
+    /// it — `gradient::Packed`/`LinearPacked`/`RadialPacked` are defined in
+    /// the `graphics` crate, which this source snapshot does not include, so
+    /// nothing here can add a field to them. Whatever builds a [`Gradient`]
+    /// from the public gradient API needs its own way to set this for a
+    /// non-`Pad` fill to ever reach [`Layer::push`]; that conversion isn't
+    /// part of this file.
+    pub spread: SpreadMode,
+
+    /// The normalized focal point `(fx, fy)` in `[-1, 1]` for radial fills,
+    /// offsetting the gradient's inner circle from the center for a
+    /// two-point conical fill. `(0.0, 0.0)` (the default) keeps the focus at
+    /// the center, degenerating to a plain radial gradient. Ignored by
+    /// linear fills.
+    pub focal: [f32; 2],
 }
 
 #[derive(Clone, Copy, Debug, Zeroable, Pod)]
@@ -29,6 +94,9 @@ pub struct LinearGradient {
 
     /// The [`Quad`] data of the [`Gradient`].
     pub quad: Quad,
+
+    /// The packed [`SpreadMode`] read per instance by the shader.
+    pub spread: u32,
 }
 
 #[derive(Clone, Copy, Debug, Zeroable, Pod)]
@@ -39,13 +107,41 @@ pub struct RadialGradient {
 
     /// The [`Quad`] data of the [`Gradient`].
     pub quad: Quad,
+
+    /// The packed [`SpreadMode`] read per instance by the shader.
+    pub spread: u32,
+
+    /// The normalized focal point `(fx, fy)` in `[-1, 1]`; equal to the
+    /// center `(0.0, 0.0)` for a plain radial fill.
+    ///
+    /// This would ideally live packed into the unused lanes of the
+    /// `gradient` field's own `Float32x4` (slot 5, "center & radii"), but
+    /// those bytes belong to `gradient::RadialPacked`'s own layout — a type
+    /// this crate doesn't define (see [`Gradient::spread`]) — so there's no
+    /// field here to repack into. Appending it instead means only
+    /// `RadialGradient` pays for the extra 8 bytes, since `LinearGradient`
+    /// and `RadialGradient` no longer share one vertex layout.
+    pub focal: [f32; 2],
 }
 
 #[derive(Debug)]
 pub struct Layer {
     linear_instances: Buffer<LinearGradient>,
     radial_instances: Buffer<RadialGradient>,
-    instance_count: usize,
+    /// Type-indexed scratch buffers the current frame's quads are classified
+    /// into before being uploaded, reused across frames to avoid per-frame
+    /// allocation.
+    linear_scratch: Vec<LinearGradient>,
+    radial_scratch: Vec<RadialGradient>,
+    /// The submission order of the quads as a run-length list of
+    /// `(strategy, count)` pairs, so interleaved linear and radial quads keep
+    /// their original painter's-algorithm layering at render time.
+    order: Vec<(GradientRenderStrategy, usize)>,
+    /// A hash of each type's scratch contents at its last upload, so a type
+    /// whose quads are byte-for-byte unchanged across frames is not
+    /// re-uploaded even when the batch is rebuilt from scratch each frame.
+    linear_hash: Option<u64>,
+    radial_hash: Option<u64>,
 }
 
 impl Layer {
@@ -67,57 +163,148 @@ impl Layer {
         Self {
             radial_instances,
             linear_instances,
-            instance_count: 0,
+            linear_scratch: Vec::new(),
+            radial_scratch: Vec::new(),
+            order: Vec::new(),
+            linear_hash: None,
+            radial_hash: None,
         }
     }
 
+    /// Clears the batched scratch buffers and order list, readying the layer
+    /// for a fresh frame's worth of [`Layer::push`]es.
+    ///
+    /// The last-uploaded content hashes are deliberately retained so a rebuilt
+    /// but unchanged batch still skips its upload in [`Layer::upload`].
+    pub fn clear(&mut self) {
+        self.linear_scratch.clear();
+        self.radial_scratch.clear();
+        self.order.clear();
+    }
+
+    /// Hashes the bytes of a type-indexed scratch buffer so an unchanged batch
+    /// can be detected and its upload skipped.
+    fn hash_instances<T: Pod>(instances: &[T]) -> u64 {
+        use std::hash::Hasher;
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        hasher.write(bytemuck::cast_slice(instances));
+        hasher.finish()
+    }
+
+    /// Whether a type's scratch buffer should be (re)uploaded: its hash
+    /// differs from the last upload (or there wasn't one) and it isn't
+    /// empty — an empty batch has nothing to write, regardless of hash.
+    fn needs_upload(last_hash: Option<u64>, new_hash: u64, is_empty: bool) -> bool {
+        last_hash != Some(new_hash) && !is_empty
+    }
+
+    /// Classifies a single gradient quad into its type-indexed scratch buffer
+    /// and extends the run-length order list.
+    ///
+    /// This lets callers batch gradients incrementally across a frame; call
+    /// [`Layer::upload`] once the batch is complete to push it to the GPU.
+    pub fn push(&mut self, gradient: &Gradient) {
+        let strategy = match gradient.gradient {
+            gradient::Packed::Linear(linear) => {
+                self.linear_scratch.push(LinearGradient {
+                    gradient: linear,
+                    quad: gradient.quad,
+                    spread: gradient.spread.pack(),
+                });
+                GradientRenderStrategy::Linear
+            }
+            gradient::Packed::Radial(radial) => {
+                self.radial_scratch.push(RadialGradient {
+                    gradient: radial,
+                    quad: gradient.quad,
+                    spread: gradient.spread.pack(),
+                    focal: gradient.focal,
+                });
+                GradientRenderStrategy::Radial
+            }
+        };
+
+        Self::push_order(&mut self.order, strategy);
+    }
+
+    /// Extends a run-length order list with one more quad of `strategy`,
+    /// collapsing it into the last run if the two match.
+    fn push_order(order: &mut Vec<(GradientRenderStrategy, usize)>, strategy: GradientRenderStrategy) {
+        match order.last_mut() {
+            Some((last, count)) if *last == strategy => *count += 1,
+            _ => order.push((strategy, 1)),
+        }
+    }
+
+    /// Uploads the batched quads, returning the run-length submission order.
+    ///
+    /// Each type's GPU buffer is resized to its own batch length and written
+    /// at the matching staging-belt offset, but only when that type's contents
+    /// differ from the last upload. A layer whose static runs are unchanged
+    /// across frames therefore generates no [`StagingBelt`] traffic, even when
+    /// the batch is rebuilt each frame via [`Layer::prepare`].
+    ///
+    /// [`StagingBelt`]: wgpu::util::StagingBelt
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
+    ) -> &[(GradientRenderStrategy, usize)] {
+        let linear_hash = Self::hash_instances(&self.linear_scratch);
+
+        if Self::needs_upload(self.linear_hash, linear_hash, self.linear_scratch.is_empty()) {
+            let _ = self.linear_instances.resize(device, self.linear_scratch.len());
+            let _ = self.linear_instances.write(
+                device,
+                encoder,
+                belt,
+                0,
+                self.linear_scratch.as_slice(),
+            );
+        }
+
+        let radial_hash = Self::hash_instances(&self.radial_scratch);
+
+        if Self::needs_upload(self.radial_hash, radial_hash, self.radial_scratch.is_empty()) {
+            let _ = self.radial_instances.resize(device, self.radial_scratch.len());
+            let _ = self.radial_instances.write(
+                device,
+                encoder,
+                belt,
+                0,
+                self.radial_scratch.as_slice(),
+            );
+        }
+
+        self.linear_hash = Some(linear_hash);
+        self.radial_hash = Some(radial_hash);
+
+        &self.order
+    }
+
+    /// Classifies `instances` in a single pass and uploads them, returning the
+    /// run-length submission order.
+    ///
+    /// This is a convenience wrapper around [`Layer::clear`], [`Layer::push`],
+    /// and [`Layer::upload`] for callers that rebuild the whole layer each
+    /// frame; [`Layer::upload`] still skips the GPU write for any type whose
+    /// rebuilt contents are unchanged.
     pub fn prepare(
         &mut self,
         device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
         instances: &[Gradient],
-    ) {
-        let linear_instances: Vec<_> = instances
-            .iter()
-            .filter_map(|gradient| {
-                if let gradient::Packed::Linear(linear) = gradient.gradient {
-                    return Some(LinearGradient {
-                        gradient: linear,
-                        quad: gradient.quad,
-                    });
-                }
-                None
-            })
-            .collect();
-        let radial_instances: Vec<_> = instances
-            .iter()
-            .filter_map(|gradient| {
-                if let gradient::Packed::Radial(radial) = gradient.gradient {
-                    return Some(RadialGradient {
-                        gradient: radial,
-                        quad: gradient.quad,
-                    });
-                }
-                None
-            })
-            .collect();
-
-        if !linear_instances.is_empty() {
-            let _ = self.linear_instances.resize(device, linear_instances.len());
-            let _ =
-                self.linear_instances
-                    .write(device, encoder, belt, 0, linear_instances.as_slice());
-        }
+    ) -> &[(GradientRenderStrategy, usize)] {
+        self.clear();
 
-        if !radial_instances.is_empty() {
-            let _ = self.linear_instances.resize(device, linear_instances.len());
-            let _ =
-                self.radial_instances
-                    .write(device, encoder, belt, 0, radial_instances.as_slice());
+        for gradient in instances {
+            self.push(gradient);
         }
 
-        self.instance_count = instances.len();
+        self.upload(device, encoder, belt)
     }
 }
 
@@ -144,22 +331,21 @@ impl Pipeline {
                 bind_group_layouts: &[constants_layout],
             });
 
-            // Create linear gradient pipeline
+            // Specialize the single shared gradient shader into one pipeline
+            // per strategy via the `gradient_type` overridable constant; the
+            // spread mode travels per instance rather than per pipeline.
             let linear_gradient_pipeline = Self::create_gradient_pipeline(
                 device,
                 &layout,
                 format,
-                "linear",
-                include_str!("../shader/quad/gradient_linear.wgsl"),
+                GradientRenderStrategy::Linear,
             );
 
-            // Create radial gradient pipeline
             let radial_gradient_pipeline = Self::create_gradient_pipeline(
                 device,
                 &layout,
                 format,
-                "radial",
-                include_str!("../shader/quad/gradient_radial.wgsl"),
+                GradientRenderStrategy::Radial,
             );
 
             Self {
@@ -172,70 +358,145 @@ impl Pipeline {
         Self {}
     }
 
+    /// The byte stride of `strategy`'s own instance type.
+    ///
+    /// `LinearGradient` and `RadialGradient` no longer share a stride — only
+    /// `RadialGradient` carries `focal` — so each strategy gets its own
+    /// vertex layout instead of one assumed to fit both.
+    #[cfg(not(target_arch = "wasm32"))]
+    const fn instance_stride(strategy: GradientRenderStrategy) -> u64 {
+        match strategy {
+            GradientRenderStrategy::Linear => std::mem::size_of::<LinearGradient>() as u64,
+            GradientRenderStrategy::Radial => std::mem::size_of::<RadialGradient>() as u64,
+        }
+    }
+
+    /// The per-instance vertex attributes for `strategy`'s own instance type.
+    ///
+    /// Both share slots 0-11; only `RadialGradient` adds slot 12 for its
+    /// `focal` field. Each strategy's list is its own `vertex_attr_array!`
+    /// invocation so the macro computes every offset from that strategy's
+    /// own attribute order, rather than splicing two separately-offset
+    /// arrays together.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn instance_attributes(strategy: GradientRenderStrategy) -> Vec<wgpu::VertexAttribute> {
+        match strategy {
+            GradientRenderStrategy::Linear => wgpu::vertex_attr_array!(
+                // Colors 1-2
+                0 => Uint32x4,
+                // Colors 3-4
+                1 => Uint32x4,
+                // Colors 5-6
+                2 => Uint32x4,
+                // Colors 7-8
+                3 => Uint32x4,
+                // Offsets 1-8
+                4 => Uint32x4,
+                // Direction
+                5 => Float32x4,
+                // Position & Scale
+                6 => Float32x4,
+                // Border color
+                7 => Float32x4,
+                // Border radius
+                8 => Float32x4,
+                // Border width
+                9 => Float32,
+                // Snap
+                10 => Uint32,
+                // Spread mode (per quad)
+                11 => Uint32,
+            )
+            .to_vec(),
+            GradientRenderStrategy::Radial => wgpu::vertex_attr_array!(
+                // Colors 1-2
+                0 => Uint32x4,
+                // Colors 3-4
+                1 => Uint32x4,
+                // Colors 5-6
+                2 => Uint32x4,
+                // Colors 7-8
+                3 => Uint32x4,
+                // Offsets 1-8
+                4 => Uint32x4,
+                // Center & radii
+                5 => Float32x4,
+                // Position & Scale
+                6 => Float32x4,
+                // Border color
+                7 => Float32x4,
+                // Border radius
+                8 => Float32x4,
+                // Border width
+                9 => Float32,
+                // Snap
+                10 => Uint32,
+                // Spread mode (per quad)
+                11 => Uint32,
+                // Focal point
+                12 => Float32x2,
+            )
+            .to_vec(),
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     fn create_gradient_pipeline(
         device: &wgpu::Device,
         layout: &wgpu::PipelineLayout,
         format: wgpu::TextureFormat,
-        gradient_type: &str,
-        gradient_shader: &str,
+        strategy: GradientRenderStrategy,
     ) -> wgpu::RenderPipeline {
+        // A single shared gradient module; the `gradient_type` overridable
+        // constant selects the `find_t` branch, so both strategies share one
+        // parsed source.
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some(&format!("iced_wgpu.quad.gradient.{}.shader", gradient_type)),
+            label: Some(&format!(
+                "iced_wgpu.quad.gradient.{}.shader",
+                strategy.label()
+            )),
             source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(&format!(
                 "{}\n{}\n{}\n{}\n{}",
                 include_str!("../shader/quad.wgsl"),
                 include_str!("../shader/vertex.wgsl"),
-                gradient_shader,
+                include_str!("../shader/quad/gradient.wgsl"),
                 include_str!("../shader/color.wgsl"),
                 include_str!("../shader/color/linear_rgb.wgsl")
             ))),
         });
 
+        let constants = std::collections::HashMap::from([(
+            "gradient_type".to_string(),
+            strategy.constant(),
+        )]);
+
         device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some(&format!(
                 "iced_wgpu.quad.gradient.{}.pipeline",
-                gradient_type
+                strategy.label()
             )),
             layout: Some(layout),
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("gradient_vs_main"),
                 buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<Gradient>() as u64,
+                    array_stride: Self::instance_stride(strategy),
                     step_mode: wgpu::VertexStepMode::Instance,
-                    attributes: &wgpu::vertex_attr_array!(
-                        // Colors 1-2
-                        0 => Uint32x4,
-                        // Colors 3-4
-                        1 => Uint32x4,
-                        // Colors 5-6
-                        2 => Uint32x4,
-                        // Colors 7-8
-                        3 => Uint32x4,
-                        // Offsets 1-8
-                        4 => Uint32x4,
-                        // Direction (for linear) / Center & radii (for radial)
-                        5 => Float32x4,
-                        // Position & Scale
-                        6 => Float32x4,
-                        // Border color
-                        7 => Float32x4,
-                        // Border radius
-                        8 => Float32x4,
-                        // Border width
-                        9 => Float32,
-                        // Snap
-                        10 => Uint32,
-                    ),
+                    attributes: &Self::instance_attributes(strategy),
                 }],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: Some("gradient_fs_main"),
                 targets: &quad::color_target_state(format),
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
             }),
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
@@ -258,23 +519,118 @@ impl Pipeline {
         render_pass: &mut wgpu::RenderPass<'a>,
         constants: &'a wgpu::BindGroup,
         layer: &'a Layer,
-        range: Range<usize>,
-        strategy: &GradientRenderStrategy,
     ) {
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let pipeline = match strategy {
-                GradientRenderStrategy::Linear => {
-                    render_pass.set_pipeline(&self.linear_gradient_pipeline);
-                    render_pass.set_vertex_buffer(0, layer.linear_instances.slice(..));
-                }
-                GradientRenderStrategy::Radial => {
-                    render_pass.set_pipeline(&self.radial_gradient_pipeline);
-                    render_pass.set_vertex_buffer(0, layer.radial_instances.slice(..));
-                }
-            };
             render_pass.set_bind_group(0, constants, &[]);
-            render_pass.draw(0..6, range.start as u32..range.end as u32);
+
+            // Walk the recorded submission order, switching pipeline and vertex
+            // buffer per run and drawing each run against its buffer's own
+            // offset so interleaved quads keep their original layering.
+            let mut linear_cursor = 0u32;
+            let mut radial_cursor = 0u32;
+
+            for (strategy, count) in &layer.order {
+                let count = *count as u32;
+
+                match strategy {
+                    GradientRenderStrategy::Linear => {
+                        render_pass.set_pipeline(&self.linear_gradient_pipeline);
+                        render_pass.set_vertex_buffer(0, layer.linear_instances.slice(..));
+                        render_pass.draw(0..6, linear_cursor..linear_cursor + count);
+                        linear_cursor += count;
+                    }
+                    GradientRenderStrategy::Radial => {
+                        render_pass.set_pipeline(&self.radial_gradient_pipeline);
+                        render_pass.set_vertex_buffer(0, layer.radial_instances.slice(..));
+                        render_pass.draw(0..6, radial_cursor..radial_cursor + count);
+                        radial_cursor += count;
+                    }
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_order_collapses_consecutive_same_strategy_runs() {
+        let mut order = Vec::new();
+
+        Layer::push_order(&mut order, GradientRenderStrategy::Linear);
+        Layer::push_order(&mut order, GradientRenderStrategy::Linear);
+        Layer::push_order(&mut order, GradientRenderStrategy::Linear);
+
+        assert_eq!(order, vec![(GradientRenderStrategy::Linear, 3)]);
+    }
+
+    #[test]
+    fn push_order_splits_on_interleaving() {
+        let mut order = Vec::new();
+
+        Layer::push_order(&mut order, GradientRenderStrategy::Linear);
+        Layer::push_order(&mut order, GradientRenderStrategy::Radial);
+        Layer::push_order(&mut order, GradientRenderStrategy::Linear);
+        Layer::push_order(&mut order, GradientRenderStrategy::Linear);
+        Layer::push_order(&mut order, GradientRenderStrategy::Radial);
+
+        assert_eq!(
+            order,
+            vec![
+                (GradientRenderStrategy::Linear, 1),
+                (GradientRenderStrategy::Radial, 1),
+                (GradientRenderStrategy::Linear, 2),
+                (GradientRenderStrategy::Radial, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn hash_instances_is_stable_for_identical_content() {
+        let a = [1u32, 2, 3, 4];
+        let b = [1u32, 2, 3, 4];
+
+        assert_eq!(Layer::hash_instances(&a), Layer::hash_instances(&b));
+    }
+
+    #[test]
+    fn hash_instances_differs_for_changed_content() {
+        let a = [1u32, 2, 3, 4];
+        let b = [1u32, 2, 3, 5];
+
+        assert_ne!(Layer::hash_instances(&a), Layer::hash_instances(&b));
+    }
+
+    #[test]
+    fn needs_upload_skips_unchanged_non_empty_batch() {
+        let hash = Layer::hash_instances(&[1u32, 2, 3]);
+
+        assert!(!Layer::needs_upload(Some(hash), hash, false));
+    }
+
+    #[test]
+    fn needs_upload_uploads_when_content_changes() {
+        let old_hash = Layer::hash_instances(&[1u32, 2, 3]);
+        let new_hash = Layer::hash_instances(&[1u32, 2, 4]);
+
+        assert!(Layer::needs_upload(Some(old_hash), new_hash, false));
+    }
+
+    #[test]
+    fn needs_upload_uploads_on_first_call() {
+        let hash = Layer::hash_instances(&[1u32, 2, 3]);
+
+        assert!(Layer::needs_upload(None, hash, false));
+    }
+
+    #[test]
+    fn needs_upload_skips_empty_batch_even_if_hash_changed() {
+        let old_hash = Layer::hash_instances(&[1u32, 2, 3]);
+        let new_hash = Layer::hash_instances::<u32>(&[]);
+
+        assert!(!Layer::needs_upload(Some(old_hash), new_hash, true));
+    }
+}